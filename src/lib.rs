@@ -15,7 +15,9 @@
 //! some random characters prefixed to prevent a name clash
 //!
 //! `copy` will traverse recursively through a directory and copy all file
-//! contents to some destination dir. It will not follow symlinks.
+//! contents to some destination dir. By default it dereferences symlinks
+//! it encounters (see [`SymlinkMode`] on [`CopyOptions`] to skip them or
+//! recreate them as-is instead).
 //!
 //! ## Example
 //!
@@ -46,13 +48,22 @@
 //!
 //! [`env::temp_dir`]: std::env::temp_dir
 
+use filetime::FileTime;
 use futures::stream::{self, Stream, StreamExt};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
 use rand::{distributions::Alphanumeric, Rng};
-use tokio::fs::{self, DirEntry};
+use tokio::{
+    fs::{self, DirEntry},
+    sync::mpsc,
+};
 
 use std::{
     env, fmt, io,
     path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
 };
 
 /// A temporary directory with a randomly generated prefix that will
@@ -64,23 +75,338 @@ pub struct TmpDir {
 
 const LEN_RNG: usize = 10;
 
-impl TmpDir {
-    /// create a new temp dir in `env::temp_dir` with a prefix. ex. `/tmp/prefix-<random chars>`
-    pub async fn new(prefix: impl AsRef<str>) -> io::Result<Self> {
-        let mut inner = env::temp_dir();
+/// how many times to retry generating a fresh random name after a
+/// collision before giving up
+const MAX_RETRIES: u32 = 16;
+
+/// generates a freshly created directory under `parent` named
+/// `<prefix>-<random chars><suffix>`, retrying with a new random component
+/// (seeded from OS entropy) on a name collision
+///
+/// retrying on `AlreadyExists` rather than checking-then-creating avoids
+/// spurious failures under concurrency and, because it's the create call
+/// itself that fails, guarantees the directory was freshly made by us
+async fn create_with_retry(
+    parent: &Path,
+    prefix: &str,
+    suffix: &str,
+    rand_len: usize,
+    retries: u32,
+) -> io::Result<PathBuf> {
+    for _ in 0..retries {
         let s: String = {
             // shrink scope of rng
             let rng = rand::thread_rng();
             rng.sample_iter(Alphanumeric)
                 .map(char::from)
-                .take(LEN_RNG)
+                .take(rand_len)
                 .collect()
         };
 
-        inner.push(&format!("{}-{}", prefix.as_ref(), s));
+        let mut inner = parent.to_path_buf();
+        inner.push(format!("{prefix}-{s}{suffix}"));
+
+        match fs::create_dir(&inner).await {
+            Ok(()) => return Ok(inner),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!(
+            "failed to create a unique tmp dir under {} after {retries} attempts",
+            parent.display()
+        ),
+    ))
+}
+
+/// builds a [`TmpDir`], generalizing [`TmpDir::new`] with a custom parent
+/// directory, suffix, and random-suffix length
+#[derive(Debug, Clone)]
+pub struct TmpDirBuilder {
+    parent: PathBuf,
+    prefix: String,
+    suffix: String,
+    rand_len: usize,
+    retries: u32,
+}
+
+impl Default for TmpDirBuilder {
+    fn default() -> Self {
+        Self {
+            parent: env::temp_dir(),
+            prefix: String::new(),
+            suffix: String::new(),
+            rand_len: LEN_RNG,
+            retries: MAX_RETRIES,
+        }
+    }
+}
+
+impl TmpDirBuilder {
+    /// create the tmp dir under `parent` instead of [`env::temp_dir`]
+    pub fn parent(mut self, parent: impl Into<PathBuf>) -> Self {
+        self.parent = parent.into();
+        self
+    }
+
+    /// the prefix placed before the random component of the directory name
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// a suffix placed after the random component of the directory name
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// the number of random characters to use, in place of the default of
+    /// 10
+    pub fn rand_len(mut self, rand_len: usize) -> Self {
+        self.rand_len = rand_len;
+        self
+    }
 
-        fs::create_dir(&inner).await?;
-        Ok(Self { inner })
+    /// the number of attempts at a fresh random name before giving up, in
+    /// place of the default of 16
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// create the directory, retrying with a fresh random name on
+    /// collision up to the configured number of attempts
+    pub async fn create(self) -> io::Result<TmpDir> {
+        let inner = create_with_retry(
+            &self.parent,
+            &self.prefix,
+            &self.suffix,
+            self.rand_len,
+            self.retries,
+        )
+        .await?;
+        Ok(TmpDir { inner })
+    }
+}
+
+/// Options controlling how [`TmpDir::copy_with`] behaves when a destination
+/// entry already exists, and whether source metadata is preserved.
+#[derive(Debug, Clone, Copy)]
+pub struct CopyOptions {
+    /// overwrite a file that already exists at the destination
+    pub overwrite: bool,
+    /// instead of erroring when the destination already exists, skip it
+    /// silently
+    pub ignore_if_exists: bool,
+    /// propagate Unix permission bits and modification time from source to
+    /// destination after each file copy
+    pub copy_permissions: bool,
+    /// how to treat symlinks encountered in the source tree
+    pub symlinks: SymlinkMode,
+}
+
+/// how `copy`/`copy_with` handle a symlink found in the source tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkMode {
+    /// omit symlinks from the destination entirely
+    Skip,
+    /// recreate the symlink at the destination, preserving its target
+    /// rather than the file or directory it points to
+    Copy,
+    /// dereference the symlink and copy the file or directory it points
+    /// to, as if it were not a symlink. this is the default, matching
+    /// `copy`'s historical behaviour. a symlinked directory that resolves
+    /// back to one of its own ancestors is a cycle and is skipped; two
+    /// unrelated symlinks that happen to share a target are not affected
+    #[default]
+    Follow,
+}
+
+impl Default for CopyOptions {
+    /// matches the historical behaviour of [`TmpDir::copy`]: always
+    /// overwrite, don't preserve permissions, dereference symlinks
+    fn default() -> Self {
+        Self {
+            overwrite: true,
+            ignore_if_exists: false,
+            copy_permissions: false,
+            symlinks: SymlinkMode::Follow,
+        }
+    }
+}
+
+/// include/exclude glob filter used by [`TmpDir::copy_filtered`] to copy
+/// only a subset of a directory's contents
+///
+/// entries are matched against their path relative to the source
+/// directory. excludes are checked first, and a directory that matches an
+/// exclude is pruned entirely rather than descended into; if any includes
+/// are set, a file must also match one of them to be copied
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    respect_gitignore: bool,
+}
+
+impl Filter {
+    /// start building a `Filter`
+    pub fn builder() -> FilterBuilder {
+        FilterBuilder::default()
+    }
+
+    fn is_excluded(&self, rel: &Path) -> bool {
+        self.exclude.as_ref().is_some_and(|g| g.is_match(rel))
+    }
+
+    fn is_included(&self, rel: &Path) -> bool {
+        self.include.as_ref().is_none_or(|g| g.is_match(rel))
+    }
+}
+
+/// builds a [`Filter`] from include/exclude glob patterns
+#[derive(Debug)]
+pub struct FilterBuilder {
+    include: GlobSetBuilder,
+    exclude: GlobSetBuilder,
+    has_include: bool,
+    has_exclude: bool,
+    respect_gitignore: bool,
+}
+
+impl Default for FilterBuilder {
+    fn default() -> Self {
+        Self {
+            include: GlobSetBuilder::new(),
+            exclude: GlobSetBuilder::new(),
+            has_include: false,
+            has_exclude: false,
+            respect_gitignore: false,
+        }
+    }
+}
+
+impl FilterBuilder {
+    /// only copy files whose relative path matches this glob; can be
+    /// called more than once to add alternatives. directories are not
+    /// matched against includes, only against excludes
+    pub fn include(mut self, pattern: impl AsRef<str>) -> Result<Self, globset::Error> {
+        self.include.add(Glob::new(pattern.as_ref())?);
+        self.has_include = true;
+        Ok(self)
+    }
+
+    /// skip entries whose relative path matches this glob; directories are
+    /// pruned without being descended into
+    pub fn exclude(mut self, pattern: impl AsRef<str>) -> Result<Self, globset::Error> {
+        self.exclude.add(Glob::new(pattern.as_ref())?);
+        self.has_exclude = true;
+        Ok(self)
+    }
+
+    /// also honor any `.gitignore` files encountered while descending into
+    /// the source directory
+    pub fn respect_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+
+    /// build the `Filter`
+    pub fn build(self) -> Result<Filter, globset::Error> {
+        Ok(Filter {
+            include: self.has_include.then(|| self.include.build()).transpose()?,
+            exclude: self.has_exclude.then(|| self.exclude.build()).transpose()?,
+            respect_gitignore: self.respect_gitignore,
+        })
+    }
+}
+
+/// the kind of filesystem change reported by [`TmpDir::watch`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// a file or directory was created
+    Create,
+    /// a file's or directory's contents or metadata changed
+    Modify,
+    /// a file or directory was removed
+    Remove,
+    /// a file or directory was renamed
+    Rename,
+}
+
+/// a single filesystem change observed by [`TmpDir::watch`]
+#[derive(Debug, Clone)]
+pub struct Change {
+    /// what kind of change occurred
+    pub kind: ChangeKind,
+    /// the path the change occurred at
+    pub path: PathBuf,
+}
+
+/// maps a `notify` event to the `Change`s we report, dropping event kinds
+/// we don't care about and paths outside `root`
+fn changes_from_event(event: notify::Event, root: &Path) -> Vec<Change> {
+    let kind = match event.kind {
+        notify::EventKind::Create(_) => ChangeKind::Create,
+        notify::EventKind::Modify(notify::event::ModifyKind::Name(_)) => ChangeKind::Rename,
+        notify::EventKind::Modify(_) => ChangeKind::Modify,
+        notify::EventKind::Remove(_) => ChangeKind::Remove,
+        _ => return Vec::new(),
+    };
+
+    event
+        .paths
+        .into_iter()
+        .filter(|path| path.starts_with(root))
+        .map(|path| Change { kind, path })
+        .collect()
+}
+
+/// bridges a `notify` watcher running on its own thread into a [`Stream`]
+/// of [`Change`]s, keeping the watcher alive for as long as the stream is
+struct WatchStream {
+    rx: mpsc::Receiver<io::Result<Change>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl Stream for WatchStream {
+    type Item = io::Result<Change>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+/// a `.gitignore` paired with the directory it was found in, since the
+/// `ignore` crate matches a gitignore's anchored patterns against paths
+/// relative to its own root rather than the overall traversal root
+type GitignoreEntry = (PathBuf, Gitignore);
+
+/// the filtered traversal's equivalent of `list_contents`'s `to_visit`:
+/// each pending directory, the canonicalized ancestor chain down to it
+/// (for cycle detection), and the `.gitignore`s accumulated on the way
+type ToVisitFiltered = Vec<(PathBuf, Vec<PathBuf>, Vec<GitignoreEntry>)>;
+
+impl TmpDir {
+    /// create a new temp dir in `env::temp_dir` with a prefix. ex. `/tmp/prefix-<random chars>`
+    pub async fn new(prefix: impl AsRef<str>) -> io::Result<Self> {
+        TmpDir::builder().prefix(prefix.as_ref()).create().await
+    }
+
+    /// start building a `TmpDir` with a custom parent directory, suffix, or
+    /// random-suffix length. See [`TmpDirBuilder`]
+    pub fn builder() -> TmpDirBuilder {
+        TmpDirBuilder::default()
+    }
+
+    /// create a freshly named directory under `parent` named
+    /// `<prefix>-<random chars>`, retrying with a fresh name on collision
+    async fn create_randomized(parent: &Path, prefix: &str) -> io::Result<PathBuf> {
+        create_with_retry(parent, prefix, "", LEN_RNG, MAX_RETRIES).await
     }
 
     /// return inner path as `PathBuf`
@@ -88,22 +414,57 @@ impl TmpDir {
         self.as_ref().to_owned()
     }
 
-    /// list the contents of a directory, if we encounter another dir
-    /// push to our traversal list
+    /// list the contents of a directory, if we encounter another dir push
+    /// to our traversal list. symlinks are handled per `symlinks`: skipped
+    /// entirely, yielded as-is to be recreated at the destination, or
+    /// dereferenced and (if a directory) descended into
+    ///
+    /// `ancestors` holds the canonicalized path of every directory on the
+    /// chain from the traversal root down to the parent of the directory
+    /// being listed; that directory's own canonicalized path is added to
+    /// the chain before its entries are checked. a symlinked directory is only skipped when
+    /// its target is already on *this* chain (an actual cycle); two
+    /// unrelated symlinks that happen to resolve to the same target are
+    /// both still copied
     async fn list_contents(
         path: PathBuf,
-        to_visit: &mut Vec<PathBuf>,
+        symlinks: SymlinkMode,
+        ancestors: &[PathBuf],
+        to_visit: &mut Vec<(PathBuf, Vec<PathBuf>)>,
     ) -> io::Result<Vec<DirEntry>> {
+        let own_canonical = fs::canonicalize(&path).await?;
+        let mut chain = ancestors.to_vec();
+        if !chain.contains(&own_canonical) {
+            chain.push(own_canonical);
+        }
+
         let mut dir = fs::read_dir(path).await?;
         let mut files = Vec::new();
 
         while let Some(child) = dir.next_entry().await? {
-            if child.metadata().await?.is_dir() {
-                to_visit.push(child.path());
-                files.push(child);
-            } else {
-                files.push(child)
+            let is_symlink = child.file_type().await?.is_symlink();
+
+            if is_symlink {
+                match symlinks {
+                    SymlinkMode::Skip => continue,
+                    SymlinkMode::Copy => {
+                        files.push(child);
+                        continue;
+                    }
+                    SymlinkMode::Follow => {}
+                }
+            }
+
+            // `DirEntry::metadata` mirrors `symlink_metadata` on unix and
+            // never follows a symlink, so a dereferencing is_dir check has
+            // to go through the free `fs::metadata` function instead
+            if fs::metadata(child.path()).await?.is_dir() {
+                if is_symlink && chain.contains(&fs::canonicalize(child.path()).await?) {
+                    continue; // cyclic: target is already on the current path chain
+                }
+                to_visit.push((child.path(), chain.clone()));
             }
+            files.push(child);
         }
 
         Ok(files)
@@ -113,46 +474,368 @@ impl TmpDir {
     /// files that are traversable from the entry path
     fn traverse(
         path: impl Into<PathBuf>,
+        symlinks: SymlinkMode,
     ) -> impl Stream<Item = io::Result<DirEntry>> + Send + 'static {
-        stream::unfold(vec![path.into()], |mut to_visit| async {
-            let path = to_visit.pop()?;
-            let file_stream = match TmpDir::list_contents(path, &mut to_visit).await {
-                Ok(files) => stream::iter(files).map(Ok).left_stream(),
-                Err(e) => stream::once(async { Err(e) }).right_stream(),
-            };
-
-            Some((file_stream, to_visit))
-        })
+        stream::unfold(
+            vec![(path.into(), Vec::new())],
+            move |mut to_visit| async move {
+                let (path, ancestors) = to_visit.pop()?;
+                let file_stream =
+                    match TmpDir::list_contents(path, symlinks, &ancestors, &mut to_visit).await {
+                        Ok(files) => stream::iter(files).map(Ok).left_stream(),
+                        Err(e) => stream::once(async { Err(e) }).right_stream(),
+                    };
+
+                Some((file_stream, to_visit))
+            },
+        )
+        .flatten()
+    }
+
+    /// like `list_contents`, but prunes entries matching `filter`'s
+    /// excludes (including any `.gitignore` rules accumulated so far) and
+    /// only yields files that pass the includes. symlinks and cycle
+    /// breaking are handled exactly as in `list_contents`
+    ///
+    /// each accumulated `.gitignore` is kept alongside the directory it
+    /// was found in, since the `ignore` crate matches a gitignore's
+    /// anchored patterns (`/foo`) against paths relative to *that*
+    /// gitignore's own root, not the overall traversal root
+    async fn list_contents_filtered(
+        path: PathBuf,
+        base: &Path,
+        symlinks: SymlinkMode,
+        ancestors: &[PathBuf],
+        parent_ignores: &[GitignoreEntry],
+        filter: &Filter,
+        to_visit: &mut ToVisitFiltered,
+    ) -> io::Result<Vec<DirEntry>> {
+        let own_canonical = fs::canonicalize(&path).await?;
+        let mut chain = ancestors.to_vec();
+        if !chain.contains(&own_canonical) {
+            chain.push(own_canonical);
+        }
+
+        let mut ignores = parent_ignores.to_vec();
+        if filter.respect_gitignore {
+            let gitignore_path = path.join(".gitignore");
+            if fs::metadata(&gitignore_path).await.is_ok() {
+                let mut builder = GitignoreBuilder::new(&path);
+                builder.add(&gitignore_path);
+                if let Ok(gitignore) = builder.build() {
+                    ignores.push((path.clone(), gitignore));
+                }
+            }
+        }
+
+        let mut dir = fs::read_dir(&path).await?;
+        let mut files = Vec::new();
+
+        while let Some(child) = dir.next_entry().await? {
+            let child_path = child.path();
+            let rel = child_path.strip_prefix(base).unwrap_or(&child_path);
+            let is_symlink = child.file_type().await?.is_symlink();
+            // never follows a symlink, matching git's own rule that a
+            // symlink doesn't count as a directory for trailing-slash
+            // gitignore patterns
+            let is_dir_for_match = fs::symlink_metadata(&child_path).await?.is_dir();
+
+            let gitignored = ignores.iter().any(|(root, gitignore)| {
+                let rel_to_root = child_path.strip_prefix(root).unwrap_or(rel);
+                gitignore.matched(rel_to_root, is_dir_for_match).is_ignore()
+            });
+
+            if filter.is_excluded(rel) || gitignored {
+                continue;
+            }
+
+            if is_symlink {
+                match symlinks {
+                    SymlinkMode::Skip => continue,
+                    SymlinkMode::Copy => {
+                        if filter.is_included(rel) {
+                            files.push(child);
+                        }
+                        continue;
+                    }
+                    SymlinkMode::Follow => {}
+                }
+            }
+
+            // `DirEntry::metadata` mirrors `symlink_metadata` on unix and
+            // never follows a symlink, so a dereferencing is_dir check has
+            // to go through the free `fs::metadata` function instead
+            if fs::metadata(child.path()).await?.is_dir() {
+                if is_symlink && chain.contains(&fs::canonicalize(child.path()).await?) {
+                    continue; // cyclic: target is already on the current path chain
+                }
+                to_visit.push((child.path(), chain.clone(), ignores.clone()));
+                files.push(child);
+            } else if filter.is_included(rel) {
+                files.push(child);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// like `traverse`, but only yields entries that pass `filter`
+    fn traverse_filtered(
+        path: impl Into<PathBuf>,
+        filter: Filter,
+        symlinks: SymlinkMode,
+    ) -> impl Stream<Item = io::Result<DirEntry>> + Send + 'static {
+        let base = path.into();
+        let to_visit = vec![(base.clone(), Vec::new(), Vec::new())];
+
+        stream::unfold(
+            (to_visit, base, filter),
+            move |(mut to_visit, base, filter)| async move {
+                let (path, ancestors, ignores) = to_visit.pop()?;
+                let file_stream = match TmpDir::list_contents_filtered(
+                    path,
+                    &base,
+                    symlinks,
+                    &ancestors,
+                    &ignores,
+                    &filter,
+                    &mut to_visit,
+                )
+                .await
+                {
+                    Ok(files) => stream::iter(files).map(Ok).left_stream(),
+                    Err(e) => stream::once(async { Err(e) }).right_stream(),
+                };
+
+                Some((file_stream, (to_visit, base, filter)))
+            },
+        )
         .flatten()
     }
 
+    /// copies a single entry yielded by `traverse`/`traverse_filtered`
+    /// from `base_path` to its equivalent path under `dest_root`,
+    /// honoring `options`. shared so `copy_with` and `copy_filtered_with`
+    /// can't drift apart as options are added
+    async fn copy_entry(
+        file: &DirEntry,
+        base_path: &Path,
+        dest_root: &Path,
+        options: &CopyOptions,
+    ) -> io::Result<()> {
+        let file_path = file.path();
+        let diff = file_path
+            .strip_prefix(base_path)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid dir"))?;
+        let dest = dest_root.to_path_buf().join(diff);
+
+        if options.symlinks == SymlinkMode::Copy && file.file_type().await?.is_symlink() {
+            if !options.overwrite && fs::symlink_metadata(&dest).await.is_ok() {
+                if options.ignore_if_exists {
+                    return Ok(());
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", dest.display()),
+                ));
+            }
+            let _ = fs::remove_file(&dest).await;
+            let target = fs::read_link(file.path()).await?;
+            #[cfg(unix)]
+            fs::symlink(&target, &dest).await?;
+            #[cfg(not(unix))]
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "SymlinkMode::Copy is only supported on unix",
+            ));
+            return Ok(());
+        }
+
+        // `DirEntry::metadata` mirrors `symlink_metadata` on unix and never
+        // follows a symlink, so a dereferencing is_dir check has to go
+        // through the free `fs::metadata` function instead
+        let metadata = fs::metadata(file.path()).await?;
+
+        if metadata.is_dir() {
+            fs::create_dir_all(dest).await?;
+            return Ok(());
+        }
+
+        if !options.overwrite && fs::metadata(&dest).await.is_ok() {
+            if options.ignore_if_exists {
+                return Ok(());
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", dest.display()),
+            ));
+        }
+
+        fs::copy(file.path(), &dest).await?;
+
+        if options.copy_permissions {
+            fs::set_permissions(&dest, metadata.permissions()).await?;
+            filetime::set_file_mtime(&dest, FileTime::from_last_modification_time(&metadata))?;
+        }
+
+        Ok(())
+    }
+
     /// recursively copies contents from tmp dir to another
     pub async fn copy(&self, dest_dir: impl AsRef<Path>) -> io::Result<()> {
+        self.copy_with(dest_dir, CopyOptions::default()).await
+    }
+
+    /// recursively copies only the entries that pass `filter` from the tmp
+    /// dir to another. see [`Filter`] for how include/exclude globs and
+    /// `.gitignore` rules are applied
+    pub async fn copy_filtered(
+        &self,
+        dest_dir: impl AsRef<Path>,
+        filter: Filter,
+    ) -> io::Result<()> {
+        self.copy_filtered_with(dest_dir, filter, CopyOptions::default())
+            .await
+    }
+
+    /// like `copy_filtered`, but also controlled by `options`. See
+    /// [`CopyOptions`] for what can be configured
+    pub async fn copy_filtered_with(
+        &self,
+        dest_dir: impl AsRef<Path>,
+        filter: Filter,
+        options: CopyOptions,
+    ) -> io::Result<()> {
+        fs::create_dir_all(dest_dir.as_ref()).await?;
+
+        let files = TmpDir::traverse_filtered(self.inner.clone(), filter, options.symlinks);
+        tokio::pin!(files);
+
+        while let Some(file) = files.next().await {
+            let file = file?;
+            TmpDir::copy_entry(&file, &self.inner, dest_dir.as_ref(), &options).await?;
+        }
+        Ok(())
+    }
+
+    /// recursively copies contents from tmp dir to another, controlled by
+    /// `options`. See [`CopyOptions`] for what can be configured
+    pub async fn copy_with(
+        &self,
+        dest_dir: impl AsRef<Path>,
+        options: CopyOptions,
+    ) -> io::Result<()> {
         // create dest dir if it doesn't exist
         fs::create_dir_all(dest_dir.as_ref()).await?;
 
-        let files = TmpDir::traverse(self.inner.clone());
+        let files = TmpDir::traverse(self.inner.clone(), options.symlinks);
         tokio::pin!(files);
 
         while let Some(file) = files.next().await {
             let file = file?;
-            let base_path = self.inner.to_path_buf();
-            let file_path = file.path();
+            TmpDir::copy_entry(&file, &self.inner, dest_dir.as_ref(), &options).await?;
+        }
+        Ok(())
+    }
 
-            // get common base path
-            let diff = file_path
-                .strip_prefix(&base_path)
-                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid dir"))?;
+    /// atomically publish the contents of this tmp dir at `dest`
+    ///
+    /// unlike `copy`, which writes the destination file-by-file and can
+    /// leave it half populated if the process dies partway through,
+    /// `persist` copies the full tree into a staging directory created
+    /// next to `dest` (so it lives on the same filesystem) and then swaps
+    /// it into place with a single `fs::rename`. if `dest` already exists
+    /// it is renamed aside first and only removed once the new directory
+    /// is safely in place, so a failure along the way never leaves `dest`
+    /// missing or a mix of old and new contents
+    pub async fn persist(&self, dest: impl AsRef<Path>) -> io::Result<()> {
+        let dest = dest.as_ref();
+        let parent = match dest.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent).await?;
+
+        let staging = TmpDir::create_randomized(parent, ".tmpdir-persist").await?;
+        if let Err(e) = self.copy(&staging).await {
+            let _ = fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
 
-            let dest = dest_dir.as_ref().to_path_buf().join(diff);
+        let backup = TmpDir::create_randomized(parent, ".tmpdir-persist-old").await?;
+        fs::remove_dir(&backup).await?; // just reserving a unique name, not the dir itself
 
-            if file.metadata().await?.is_dir() {
-                fs::create_dir_all(dest).await?;
-            } else {
-                fs::copy(file.path(), dest).await?;
+        let dest_existed = fs::metadata(dest).await.is_ok();
+        if dest_existed {
+            if let Err(e) = fs::rename(dest, &backup).await {
+                let _ = fs::remove_dir_all(&staging).await;
+                return Err(e);
+            }
+        }
+
+        match fs::rename(&staging, dest).await {
+            Ok(()) => {
+                if dest_existed {
+                    fs::remove_dir_all(&backup).await?;
+                }
+                Ok(())
+            }
+            Err(e) => {
+                // roll back so callers never observe a missing `dest`
+                if dest_existed {
+                    let _ = fs::rename(&backup, dest).await;
+                }
+                let _ = fs::remove_dir_all(&staging).await;
+
+                #[cfg(unix)]
+                if e.raw_os_error() == Some(libc::EXDEV) {
+                    return Err(io::Error::other(
+                        "persist requires the staging directory and dest to be on the same filesystem (cross-device rename)",
+                    ));
+                }
+                Err(e)
             }
         }
-        Ok(())
+    }
+
+    /// watch this tmp dir's subtree for filesystem changes, returning a
+    /// [`Stream`] of [`Change`]s as they happen
+    ///
+    /// backed by a `notify` watcher running on its own thread, bridged into
+    /// the returned stream over a channel. the watcher is torn down as soon
+    /// as the stream is dropped
+    pub fn watch(&self) -> impl Stream<Item = io::Result<Change>> + Send + 'static {
+        let root = self.inner.clone();
+        let (tx, rx) = mpsc::channel(32);
+
+        let watcher = notify::recommended_watcher({
+            let root = root.clone();
+            move |res: notify::Result<notify::Event>| {
+                let event = match res {
+                    Ok(event) => event,
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(io::Error::other(e)));
+                        return;
+                    }
+                };
+                for change in changes_from_event(event, &root) {
+                    let _ = tx.blocking_send(Ok(change));
+                }
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+
+        match watcher {
+            Ok(watcher) => WatchStream {
+                rx,
+                _watcher: watcher,
+            }
+            .left_stream(),
+            Err(e) => stream::once(async move { Err(io::Error::other(e)) }).right_stream(),
+        }
     }
 
     /// close the tmp dir and nuke it's contents
@@ -247,4 +930,355 @@ mod tests {
         tmp.close().await.unwrap();
         tmp2.close().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_copy_with_no_overwrite() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("file1")).await.unwrap();
+        file.write_all(b"new").await.unwrap();
+
+        let tmp2 = TmpDir::new("bar").await.unwrap();
+        let mut file = fs::File::create(tmp2.as_ref().join("file1")).await.unwrap();
+        file.write_all(b"old").await.unwrap();
+
+        let err = tmp
+            .copy_with(
+                tmp2.as_ref(),
+                CopyOptions {
+                    overwrite: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+        tmp.copy_with(
+            tmp2.as_ref(),
+            CopyOptions {
+                overwrite: false,
+                ignore_if_exists: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let contents = fs::read(tmp2.as_ref().join("file1")).await.unwrap();
+        assert_eq!(contents, b"old");
+
+        tmp.close().await.unwrap();
+        tmp2.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_filtered() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let tmp_dir = tmp.as_ref().to_path_buf();
+
+        fs::create_dir(tmp_dir.join("src")).await.unwrap();
+        fs::File::create(tmp_dir.join("src").join("main.rs"))
+            .await
+            .unwrap();
+        fs::create_dir(tmp_dir.join("target")).await.unwrap();
+        fs::File::create(tmp_dir.join("target").join("build.o"))
+            .await
+            .unwrap();
+
+        let filter = Filter::builder()
+            .exclude("target")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let tmp2 = TmpDir::new("bar").await.unwrap();
+        tmp.copy_filtered(tmp2.as_ref(), filter).await.unwrap();
+
+        assert!(fs::metadata(tmp2.as_ref().join("src").join("main.rs"))
+            .await
+            .is_ok());
+        assert!(fs::metadata(tmp2.as_ref().join("target")).await.is_err());
+
+        tmp.close().await.unwrap();
+        tmp2.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_filtered_respects_nested_gitignore_anchors() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let tmp_dir = tmp.as_ref().to_path_buf();
+
+        // an anchored pattern in a nested .gitignore must only match paths
+        // relative to that .gitignore's own directory, not the traversal
+        // root: `/generated` here should ignore `src/generated`, but must
+        // not affect a same-named `generated` anywhere else in the tree
+        fs::create_dir(tmp_dir.join("src")).await.unwrap();
+        let mut gitignore = fs::File::create(tmp_dir.join("src").join(".gitignore"))
+            .await
+            .unwrap();
+        gitignore.write_all(b"/generated\n").await.unwrap();
+        fs::create_dir(tmp_dir.join("src").join("generated"))
+            .await
+            .unwrap();
+        fs::File::create(tmp_dir.join("src").join("generated").join("out.rs"))
+            .await
+            .unwrap();
+        fs::File::create(tmp_dir.join("src").join("main.rs"))
+            .await
+            .unwrap();
+
+        fs::create_dir(tmp_dir.join("generated")).await.unwrap();
+        fs::File::create(tmp_dir.join("generated").join("keep.rs"))
+            .await
+            .unwrap();
+
+        let filter = Filter::builder().respect_gitignore(true).build().unwrap();
+
+        let tmp2 = TmpDir::new("bar").await.unwrap();
+        tmp.copy_filtered(tmp2.as_ref(), filter).await.unwrap();
+
+        assert!(fs::metadata(tmp2.as_ref().join("src").join("main.rs"))
+            .await
+            .is_ok());
+        assert!(fs::metadata(tmp2.as_ref().join("src").join("generated"))
+            .await
+            .is_err());
+        assert!(
+            fs::metadata(tmp2.as_ref().join("generated").join("keep.rs"))
+                .await
+                .is_ok()
+        );
+
+        tmp.close().await.unwrap();
+        tmp2.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_filtered_with_honors_copy_options() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("file1")).await.unwrap();
+        file.write_all(b"new").await.unwrap();
+
+        let tmp2 = TmpDir::new("bar").await.unwrap();
+        let mut existing = fs::File::create(tmp2.as_ref().join("file1")).await.unwrap();
+        existing.write_all(b"old").await.unwrap();
+
+        let filter = Filter::builder().build().unwrap();
+        let err = tmp
+            .copy_filtered_with(
+                tmp2.as_ref(),
+                filter,
+                CopyOptions {
+                    overwrite: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+        let contents = fs::read(tmp2.as_ref().join("file1")).await.unwrap();
+        assert_eq!(contents, b"old");
+
+        tmp.close().await.unwrap();
+        tmp2.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_persist_replaces_existing_dest() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("new")).await.unwrap();
+        file.write_all(b"new").await.unwrap();
+
+        let published = TmpDir::new("published").await.unwrap();
+        let dest = published.as_ref().join("out");
+        fs::create_dir(&dest).await.unwrap();
+        let mut old = fs::File::create(dest.join("old")).await.unwrap();
+        old.write_all(b"old").await.unwrap();
+
+        tmp.persist(&dest).await.unwrap();
+
+        assert!(fs::metadata(dest.join("new")).await.is_ok());
+        assert!(fs::metadata(dest.join("old")).await.is_err());
+
+        tmp.close().await.unwrap();
+        published.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_create() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let changes = tmp.watch();
+        tokio::pin!(changes);
+
+        // give the watcher time to start before we trigger an event
+        time::sleep(Duration::from_millis(200)).await;
+        let mut file = fs::File::create(tmp.as_ref().join("file1")).await.unwrap();
+        file.write_all(b"foo").await.unwrap();
+
+        let change = time::timeout(Duration::from_secs(5), changes.next())
+            .await
+            .expect("timed out waiting for a change event")
+            .expect("stream ended unexpectedly")
+            .unwrap();
+        assert_eq!(change.kind, ChangeKind::Create);
+        assert_eq!(change.path, tmp.as_ref().join("file1"));
+
+        tmp.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_builder_custom_parent_and_suffix() {
+        let parent = TmpDir::new("parent").await.unwrap();
+
+        let tmp = TmpDir::builder()
+            .parent(parent.to_path_buf())
+            .prefix("foo")
+            .suffix(".d")
+            .rand_len(4)
+            .create()
+            .await
+            .unwrap();
+
+        assert!(tmp.as_ref().starts_with(parent.as_ref()));
+        let name = tmp.as_ref().file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("foo-"));
+        assert!(name.ends_with(".d"));
+        // "foo-" + 4 random chars + ".d"
+        assert_eq!(name.len(), "foo-".len() + 4 + ".d".len());
+
+        tmp.close().await.unwrap();
+        parent.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_symlink_modes() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("real")).await.unwrap();
+        file.write_all(b"foo").await.unwrap();
+        std::os::unix::fs::symlink(tmp.as_ref().join("real"), tmp.as_ref().join("link")).unwrap();
+
+        let skip = TmpDir::new("skip").await.unwrap();
+        tmp.copy_with(
+            skip.as_ref(),
+            CopyOptions {
+                symlinks: SymlinkMode::Skip,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(fs::metadata(skip.as_ref().join("link")).await.is_err());
+
+        let copy_as_link = TmpDir::new("copy").await.unwrap();
+        tmp.copy_with(
+            copy_as_link.as_ref(),
+            CopyOptions {
+                symlinks: SymlinkMode::Copy,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let link_target = fs::read_link(copy_as_link.as_ref().join("link"))
+            .await
+            .unwrap();
+        assert_eq!(link_target, tmp.as_ref().join("real"));
+
+        let follow = TmpDir::new("follow").await.unwrap();
+        tmp.copy(follow.as_ref()).await.unwrap();
+        assert_eq!(
+            fs::read(follow.as_ref().join("link")).await.unwrap(),
+            b"foo"
+        );
+        assert!(fs::symlink_metadata(follow.as_ref().join("link"))
+            .await
+            .unwrap()
+            .file_type()
+            .is_file());
+
+        tmp.close().await.unwrap();
+        skip.close().await.unwrap();
+        copy_as_link.close().await.unwrap();
+        follow.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_follows_symlinks_but_breaks_cycles() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        fs::create_dir(tmp.as_ref().join("dir")).await.unwrap();
+        // dir/loop points back at dir itself, an actual cycle
+        std::os::unix::fs::symlink(
+            tmp.as_ref().join("dir"),
+            tmp.as_ref().join("dir").join("loop"),
+        )
+        .unwrap();
+
+        let dest = TmpDir::new("bar").await.unwrap();
+        // must terminate rather than recurse forever
+        tmp.copy(dest.as_ref()).await.unwrap();
+        assert!(fs::metadata(dest.as_ref().join("dir")).await.is_ok());
+
+        tmp.close().await.unwrap();
+        dest.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_follows_duplicate_symlink_targets() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("shared.conf"))
+            .await
+            .unwrap();
+        file.write_all(b"shared").await.unwrap();
+        // two unrelated symlinks to the same file, not a cycle
+        std::os::unix::fs::symlink(tmp.as_ref().join("shared.conf"), tmp.as_ref().join("link1"))
+            .unwrap();
+        std::os::unix::fs::symlink(tmp.as_ref().join("shared.conf"), tmp.as_ref().join("link2"))
+            .unwrap();
+
+        let dest = TmpDir::new("bar").await.unwrap();
+        tmp.copy(dest.as_ref()).await.unwrap();
+
+        assert_eq!(
+            fs::read(dest.as_ref().join("link1")).await.unwrap(),
+            b"shared"
+        );
+        assert_eq!(
+            fs::read(dest.as_ref().join("link2")).await.unwrap(),
+            b"shared"
+        );
+
+        tmp.close().await.unwrap();
+        dest.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_follows_symlinked_directory() {
+        let tmp = TmpDir::new("foo").await.unwrap();
+        fs::create_dir(tmp.as_ref().join("real_dir")).await.unwrap();
+        let mut file = fs::File::create(tmp.as_ref().join("real_dir").join("file1"))
+            .await
+            .unwrap();
+        file.write_all(b"foo").await.unwrap();
+        // a plain symlink to an unrelated directory, no cycle at all
+        std::os::unix::fs::symlink(tmp.as_ref().join("real_dir"), tmp.as_ref().join("link_dir"))
+            .unwrap();
+
+        let dest = TmpDir::new("bar").await.unwrap();
+        tmp.copy(dest.as_ref()).await.unwrap();
+
+        assert_eq!(
+            fs::read(dest.as_ref().join("link_dir").join("file1"))
+                .await
+                .unwrap(),
+            b"foo"
+        );
+        assert!(fs::symlink_metadata(dest.as_ref().join("link_dir"))
+            .await
+            .unwrap()
+            .file_type()
+            .is_dir());
+
+        tmp.close().await.unwrap();
+        dest.close().await.unwrap();
+    }
 }